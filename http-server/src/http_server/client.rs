@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::io;
+use std::io::Write;
+use std::net::TcpStream;
+
+use super::{ CRLF, HttpMethod, HttpVersion };
+use super::http_header::{ self, HttpHeader };
+use super::http_message;
+use super::to_bytes::ToBytes;
+
+struct ParsedUrl {
+    host: String,
+    port: u16,
+    path: String
+}
+
+fn parse_url(url: &str) -> Result<ParsedUrl, io::Error> {
+    let rest = url.strip_prefix("http://")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "only http:// URLs are supported"))?;
+
+    let (authority, path) = match rest.split_once('/') {
+        Some((authority, path)) => (authority, format!("/{}", path)),
+        None => (rest, "/".to_string())
+    };
+
+    let (host, port) = match authority.split_once(':') {
+        Some((host, port)) => {
+            let port = port.parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid port"))?;
+            (host.to_string(), port)
+        },
+        None => (authority.to_string(), 80)
+    };
+
+    Ok(ParsedUrl { host, port, path })
+}
+
+// Builds and sends an outbound HTTP request. Construct with `client::get`/
+// `client::post`/etc., add headers/a body, then call `send`.
+pub struct ClientRequestBuilder {
+    method: HttpMethod,
+    url: String,
+    header: HttpHeader,
+    body: Vec<u8>,
+    max_response_body_size: Option<usize>
+}
+
+impl ClientRequestBuilder {
+    pub fn new(method: HttpMethod, url: &str) -> Self {
+        ClientRequestBuilder {
+            method,
+            url: url.to_string(),
+            header: http_header::new(),
+            body: Vec::new(),
+            max_response_body_size: None
+        }
+    }
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.header.set(name, value);
+        self
+    }
+    pub fn body(mut self, data: impl ToBytes) -> Self {
+        self.body = data.to_bytes().to_vec();
+        self
+    }
+    // Caps the accepted size of the response body; unset by default, since
+    // this is a general-purpose client and a server it talks to may
+    // legitimately return a large-but-expected response.
+    pub fn max_response_body_size(mut self, max: usize) -> Self {
+        self.max_response_body_size = Some(max);
+        self
+    }
+    pub fn send(mut self) -> Result<ClientResponse, Box<dyn Error>> {
+        let parsed = parse_url(&self.url)?;
+        let mut socket = TcpStream::connect((parsed.host.as_str(), parsed.port))?;
+
+        if self.header.get("host").is_empty() {
+            self.header.set("Host", &parsed.host);
+        }
+        if self.header.get("connection").is_empty() {
+            self.header.set("Connection", "close");
+        }
+        if !self.body.is_empty() && self.header.get("content-length").is_empty() {
+            self.header.set("Content-Length", &self.body.len().to_string());
+        }
+
+        let request_line = format!("{} {} {}{}", self.method.to_str(), parsed.path, HttpVersion::V1_1.to_str(), CRLF);
+        socket.write(request_line.as_bytes())?;
+
+        for (key, values) in self.header.to_map() {
+            if key.is_empty() {
+                continue;
+            }
+            for value in values {
+                if !value.is_empty() {
+                    socket.write(format!("{}: {}{}", key, value, CRLF).as_bytes())?;
+                }
+            }
+        }
+        socket.write(CRLF.as_bytes())?;
+
+        if !self.body.is_empty() {
+            socket.write(&self.body)?;
+        }
+
+        ClientResponse::read_from(socket, self.max_response_body_size)
+    }
+}
+
+pub fn get(url: &str) -> ClientRequestBuilder {
+    ClientRequestBuilder::new(HttpMethod::GET, url)
+}
+pub fn post(url: &str) -> ClientRequestBuilder {
+    ClientRequestBuilder::new(HttpMethod::POST, url)
+}
+pub fn put(url: &str) -> ClientRequestBuilder {
+    ClientRequestBuilder::new(HttpMethod::PUT, url)
+}
+pub fn delete(url: &str) -> ClientRequestBuilder {
+    ClientRequestBuilder::new(HttpMethod::DELETE, url)
+}
+
+// An HTTP response read off a socket opened by `ClientRequestBuilder::send`.
+pub struct ClientResponse {
+    status: u16,
+    pub header: HttpHeader,
+    pub body: Vec<u8>
+}
+
+impl ClientResponse {
+    fn read_from(mut socket: TcpStream, max_body_size: Option<usize>) -> Result<ClientResponse, Box<dyn Error>> {
+        let header_buf = match http_message::read_header_block(&mut socket)? {
+            Some(buf) => buf,
+            None => return Err(Box::new(io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed before a response was received")))
+        };
+        let header_buf = String::from_utf8(header_buf)?;
+        let mut lines = header_buf.split(CRLF);
+
+        // Parse the status line => HTTP/1.1 200 OK
+        let status_line = lines.next().unwrap_or("");
+        let words: Vec<&str> = status_line.splitn(3, ' ').collect();
+        if words.len() < 2 {
+            let custom_err = io::Error::new(io::ErrorKind::InvalidData, "Invalid HTTP status line");
+            return Err(Box::new(custom_err));
+        }
+        let status = words[1].parse::<u16>()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid HTTP status code"))?;
+
+        let mut header = http_header::new();
+        let remaining_lines: Vec<&str> = lines.collect();
+        http_message::parse_header_fields(&remaining_lines, &mut header);
+
+        let body = http_message::read_body(&mut socket, &header, max_body_size)?;
+
+        Ok(ClientResponse { status, header, body })
+    }
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+}
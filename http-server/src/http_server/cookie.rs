@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::ops::Add;
+use std::time::SystemTime;
+
+use super::http_date;
+
+// Parses a request `Cookie` header ("name1=value1; name2=value2") into a lookup
+// map. Unparseable pairs (no "=") are skipped rather than failing the request.
+pub fn parse_cookie_header(value: &str) -> HashMap<String, String> {
+    let mut cookies = HashMap::new();
+    for pair in value.split(';') {
+        let pair = pair.trim();
+        if let Some((name, value)) = pair.split_once('=') {
+            cookies.insert(name.trim().to_string(), value.trim().to_string());
+        }
+    }
+    cookies
+}
+
+pub enum SameSite {
+    Strict,
+    Lax,
+    None
+}
+
+impl SameSite {
+    fn to_str(&self) -> &'static str {
+        match self {
+            Self::Strict => "Strict",
+            Self::Lax => "Lax",
+            Self::None => "None"
+        }
+    }
+}
+
+// A `Set-Cookie` value under construction. Build with `Cookie::new` and the
+// attribute methods below, then hand it to `HttpResponse::set_cookie`.
+pub struct Cookie {
+    name: String,
+    value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    max_age: Option<i64>,
+    expires: Option<SystemTime>,
+    http_only: bool,
+    secure: bool,
+    same_site: Option<SameSite>
+}
+
+impl Cookie {
+    // Strips characters that would let `name`/`value` break out of the
+    // Set-Cookie header line (`\r`/`\n`, which could inject extra header
+    // lines -- response splitting) or be misread as an attribute separator
+    // by the parser on the other end (`;`/`,`).
+    fn sanitize_token(token: &str) -> String {
+        token.chars().filter(|c| !matches!(c, '\r' | '\n' | ';' | ',')).collect()
+    }
+    pub fn new(name: &str, value: &str) -> Self {
+        Cookie {
+            name: Self::sanitize_token(name),
+            value: Self::sanitize_token(value),
+            path: None,
+            domain: None,
+            max_age: None,
+            expires: None,
+            http_only: false,
+            secure: false,
+            same_site: None
+        }
+    }
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+    pub fn domain(mut self, domain: &str) -> Self {
+        self.domain = Some(domain.to_string());
+        self
+    }
+    pub fn max_age(mut self, seconds: i64) -> Self {
+        self.max_age = Some(seconds);
+        self
+    }
+    pub fn expires(mut self, time: SystemTime) -> Self {
+        self.expires = Some(time);
+        self
+    }
+    pub fn http_only(mut self, value: bool) -> Self {
+        self.http_only = value;
+        self
+    }
+    pub fn secure(mut self, value: bool) -> Self {
+        self.secure = value;
+        self
+    }
+    pub fn same_site(mut self, value: SameSite) -> Self {
+        self.same_site = Some(value);
+        self
+    }
+    pub(crate) fn to_set_cookie_header(&self) -> String {
+        let mut value = String::new().add(&self.name).add("=").add(&self.value);
+
+        if let Some(path) = &self.path {
+            value = value.add("; Path=").add(path);
+        }
+        if let Some(domain) = &self.domain {
+            value = value.add("; Domain=").add(domain);
+        }
+        if let Some(max_age) = self.max_age {
+            value = value.add("; Max-Age=").add(&max_age.to_string());
+        }
+        if let Some(expires) = self.expires {
+            value = value.add("; Expires=").add(&http_date::format_http_date(expires));
+        }
+        if self.http_only {
+            value = value.add("; HttpOnly");
+        }
+        if self.secure {
+            value = value.add("; Secure");
+        }
+        if let Some(same_site) = &self.same_site {
+            value = value.add("; SameSite=").add(same_site.to_str());
+        }
+
+        value
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 pub struct HttpHeader {
-    data: HashMap<String, String>,
+    data: HashMap<String, Vec<String>>,
 }
 
 pub fn new() -> HttpHeader {
@@ -10,18 +10,30 @@ pub fn new() -> HttpHeader {
 
 impl HttpHeader {
     pub fn get(&self, header: &str) -> &str {
-        match self.data.get(&header.to_lowercase()) {
+        match self.data.get(&header.to_lowercase()).and_then(|values| values.first()) {
             Some(val) => val,
             None => ""
         }
     }
+    // Every value set for `header`, in insertion order. Headers like
+    // `Set-Cookie` legitimately repeat; `get` only ever sees the first one.
+    pub fn get_all(&self, header: &str) -> &[String] {
+        match self.data.get(&header.to_lowercase()) {
+            Some(values) => values,
+            None => &[]
+        }
+    }
     pub fn set(&mut self, header: &str, value: &str) {
-        self.data.insert(header.to_lowercase(), value.to_string());
+        self.data.insert(header.to_lowercase(), vec![value.to_string()]);
+    }
+    // Appends another value for `header` instead of replacing what's there.
+    pub fn add(&mut self, header: &str, value: &str) {
+        self.data.entry(header.to_lowercase()).or_insert_with(Vec::new).push(value.to_string());
     }
     pub fn remove(&mut self, header: &str) {
         self.data.remove(&header.to_lowercase());
     }
-    pub fn to_map(&self) -> &HashMap<String, String> {
+    pub fn to_map(&self) -> &HashMap<String, Vec<String>> {
         &self.data
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,163 @@
+use std::io;
+use std::io::Read;
+
+use super::http_header::HttpHeader;
+
+// Low-level header/body parsing shared between the server's request parser
+// and the client's response parser -- everything here works on any `Read`
+// so the same logic reads a `TcpStream` on the server side and a freshly
+// connected one on the client side.
+
+// Parses "Key: Value" lines (as split from a header block by CRLF) into
+// `header`, splitting each line at its first ':' so values that themselves
+// contain colons (HTTP dates, "host:port") are kept whole. Lines with no ':'
+// are ignored.
+pub(crate) fn parse_header_fields(lines: &[&str], header: &mut HttpHeader) {
+    for line in lines {
+        let (field, value) = match line.split_once(':') {
+            Some(field_value) => field_value,
+            None => continue
+        };
+        // TODO: Deal with the HTTP fields which has multiple values or key-value pairs within the value part
+        header.set(field.trim(), value.trim());
+    }
+}
+
+// Reads raw header bytes up to and including the terminating blank line.
+// Returns `Ok(None)` if the stream ended before any bytes were read at all
+// (a clean close); an incomplete header past that point is an error.
+pub(crate) fn read_header_block<R: Read>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    const DOUBLE_CRLF_ASCII: [u8; 4] = [b'\r', b'\n', b'\r', b'\n'];
+
+    let mut buf: Vec<u8> = Vec::new();
+    for byte in Read::by_ref(reader).bytes() {
+        buf.push(byte?);
+        if buf.ends_with(&DOUBLE_CRLF_ASCII) {
+            return Ok(Some(buf));
+        }
+    }
+
+    if buf.is_empty() {
+        Ok(None)
+    } else {
+        Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Incomplete HTTP header"))
+    }
+}
+
+// Reads a single CRLF-terminated line (the CRLF is stripped), one byte at a time.
+pub(crate) fn read_line<R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut line: Vec<u8> = Vec::new();
+    loop {
+        let byte = match Read::by_ref(reader).bytes().next() {
+            Some(x) => x?,
+            None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Incomplete line"))
+        };
+        if byte == b'\n' {
+            break;
+        }
+        if byte != b'\r' {
+            line.push(byte);
+        }
+    }
+    String::from_utf8(line).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+// Reads and decodes a `Transfer-Encoding: chunked` body. `max_body_size`, when
+// set, caps both an individual chunk's declared size and the cumulative body
+// size, the same way `read_body` caps a declared Content-Length -- without it
+// a single chunk header (e.g. "FFFFFFFF") would let the loop below buffer an
+// unbounded amount of attacker-controlled data.
+pub(crate) fn read_chunked_body<R: Read>(reader: &mut R, max_body_size: Option<usize>) -> io::Result<Vec<u8>> {
+    let mut body: Vec<u8> = Vec::new();
+    loop {
+        let size_line = read_line(reader)?;
+        // Chunk extensions (";name=value") are allowed after the size and are ignored.
+        let size_str = size_line.split(';').next().unwrap_or("").trim();
+        let size = u64::from_str_radix(size_str, 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid chunk size"))?;
+
+        if let Some(max) = max_body_size {
+            let max = max as u64;
+            if size > max || (body.len() as u64).saturating_add(size) > max {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Chunked body exceeds maximum accepted size"));
+            }
+        }
+
+        if size == 0 {
+            // Consume the (possibly empty) trailer header block up to the final CRLF.
+            loop {
+                let trailer_line = read_line(reader)?;
+                if trailer_line.is_empty() {
+                    break;
+                }
+            }
+            break;
+        }
+
+        for _ in 0..size {
+            let byte = match Read::by_ref(reader).bytes().next() {
+                Some(x) => x?,
+                None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Incomplete chunk body"))
+            };
+            body.push(byte);
+        }
+
+        // Consume the CRLF that terminates the chunk data.
+        read_line(reader)?;
+    }
+    Ok(body)
+}
+
+// Upper bound the server applies to a request's declared body size (passed as
+// `max_body_size` to `read_body`/`read_chunked_body`). Without this, a bare
+// header (or a single oversized chunk) claiming an enormous length would make
+// the body readers allocate/buffer that much memory before a single body byte
+// has arrived -- a bandwidth-free DoS. Not applied to the client's response
+// reading by default -- `ClientRequestBuilder` is a general-purpose HTTP
+// client and an unconditional ceiling would silently break large-but-legitimate
+// responses from a trusted server.
+pub(crate) const MAX_CONTENT_LENGTH: usize = 10 * 1024 * 1024;
+
+// Size of the intermediate buffer used to grow the body incrementally, so the
+// full `content_length` is only ever committed to memory as bytes actually arrive.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+// Reads exactly `content_length` bytes, growing the buffer incrementally
+// rather than allocating the whole length up front.
+pub(crate) fn read_fixed_body<R: Read>(reader: &mut R, content_length: usize) -> io::Result<Vec<u8>> {
+    let mut body: Vec<u8> = Vec::new();
+    let mut remaining = content_length;
+    let mut chunk = [0u8; READ_CHUNK_SIZE];
+    while remaining > 0 {
+        let want = remaining.min(READ_CHUNK_SIZE);
+        reader.read_exact(&mut chunk[..want])?;
+        body.extend_from_slice(&chunk[..want]);
+        remaining -= want;
+    }
+    Ok(body)
+}
+
+// Reads whichever body framing `header` declares: `Transfer-Encoding: chunked`
+// takes precedence over `Content-Length`, matching how most servers and
+// clients resolve the two when (incorrectly) both are present. `max_body_size`
+// bounds the accepted size, both the chunked case and a declared
+// Content-Length; pass `None` to accept any size the framing declares.
+pub(crate) fn read_body<R: Read>(reader: &mut R, header: &HttpHeader, max_body_size: Option<usize>) -> io::Result<Vec<u8>> {
+    if header.get("transfer-encoding").to_lowercase().contains("chunked") {
+        return read_chunked_body(reader, max_body_size);
+    }
+
+    let content_length = header.get("content-length");
+    if content_length.is_empty() {
+        return Ok(Vec::new());
+    }
+    let content_length: usize = content_length.parse()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid Content-Length"))?;
+    if let Some(max) = max_body_size {
+        if content_length > max {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Content-Length exceeds maximum accepted body size"));
+        }
+    }
+
+    read_fixed_body(reader, content_length)
+}
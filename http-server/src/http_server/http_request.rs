@@ -6,6 +6,15 @@ pub struct HttpRequest {
     pub http_version: HttpVersion,
     pub method: HttpMethod,
     pub url: String,
+    pub query: HashMap<String, String>,
+    pub params: HashMap<String, String>,
+    pub cookies: HashMap<String, String>,
     pub header: HttpHeader,
     pub body: Vec<u8>
-}
\ No newline at end of file
+}
+
+impl HttpRequest {
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.cookies.get(name).map(|value| value.as_str())
+    }
+}
@@ -11,6 +11,7 @@ use super::*;
 use super::http_request::HttpRequest;
 use super::http_header::HttpHeader;
 use super::to_bytes::ToBytes;
+use super::cookie::Cookie;
 
 const IANA_HTTP_RESPONSE_STATUS: [(u16, &str); 12] = [
     (200, "OK"),
@@ -81,10 +82,15 @@ impl<'a> HttpResponse<'a> {
                         .add(&self.status.reason).add(CRLF);
         sock.write(line.as_bytes())?;
 
-        for (key, value) in self.header.to_map() {
-            if !key.is_empty() && !value.is_empty() {
-                line = key.to_string().add(": ").add(value).add(CRLF);
-                sock.write(line.as_bytes())?;
+        for (key, values) in self.header.to_map() {
+            if key.is_empty() {
+                continue;
+            }
+            for value in values {
+                if !value.is_empty() {
+                    line = key.to_string().add(": ").add(value).add(CRLF);
+                    sock.write(line.as_bytes())?;
+                }
             }
         }
         sock.write(CRLF.as_bytes())?;
@@ -181,6 +187,12 @@ impl<'a> HttpResponse<'a> {
         self.header.set("Content-Type", value);
         self
     }
+    // Appends a `Set-Cookie` header; unlike the other header setters this can
+    // be called more than once, since a response may set several cookies.
+    pub fn set_cookie(&mut self, cookie: Cookie) -> &mut Self {
+        self.header.add("Set-Cookie", &cookie.to_set_cookie_header());
+        self
+    }
     pub fn redirect(&mut self, location: &str) -> Result<(), io::Error> {
         self.status(302);
         self.header.set("Location", location);
@@ -205,4 +217,46 @@ impl<'a> HttpResponse<'a> {
         self.header.set("Content-Disposition", &(format!("attachment; filename={}", file_name)));
         self.send_file(path)
     }
+    fn etag_for(metadata: &std::fs::Metadata) -> Result<String, io::Error> {
+        let modified_secs = metadata.modified()?.duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or(std::time::Duration::from_secs(0)).as_secs();
+        Ok(format!("W/\"{:x}-{:x}\"", metadata.len(), modified_secs))
+    }
+    // Serves a static file with `Last-Modified`/`ETag` and honors `If-None-Match` /
+    // `If-Modified-Since`, replying `304 Not Modified` with no body when the
+    // client's cached copy is still fresh. `If-None-Match` takes precedence.
+    pub fn send_file_cached(&mut self, request: &HttpRequest, path: &Path) -> Result<(), io::Error> {
+        let metadata = path.metadata()?;
+        let etag = Self::etag_for(&metadata)?;
+        let last_modified = metadata.modified()?;
+
+        let if_none_match = request.header.get("if-none-match");
+        let not_modified = if !if_none_match.is_empty() {
+            if_none_match == etag
+        } else {
+            let if_modified_since = request.header.get("if-modified-since");
+            !if_modified_since.is_empty() && http_date::parse_http_date(if_modified_since)
+                .map_or(false, |since| {
+                    // `since` is truncated to whole seconds by parse_http_date, and
+                    // Last-Modified is emitted the same way, so floor both sides before
+                    // comparing -- otherwise a non-zero fractional mtime never compares
+                    // as <=, and 304-via-If-Modified-Since never fires.
+                    let last_modified_secs = last_modified.duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or(std::time::Duration::from_secs(0)).as_secs();
+                    let since_secs = since.duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or(std::time::Duration::from_secs(0)).as_secs();
+                    last_modified_secs <= since_secs
+                })
+        };
+
+        if not_modified {
+            self.header.set("ETag", &etag);
+            self.header.set("Last-Modified", &http_date::format_http_date(last_modified));
+            return self.status(304).end();
+        }
+
+        self.header.set("ETag", &etag);
+        self.header.set("Last-Modified", &http_date::format_http_date(last_modified));
+        self.send_file(path)
+    }
 }
\ No newline at end of file
@@ -7,25 +7,35 @@ use std::thread;
 use std::error::Error;
 use std::sync::Arc;
 use std::ops::{ Add, Deref };
-use std::io::Read;
+use std::io::{ Read, Write };
 use std::path::{ Path, PathBuf };
+use std::time::Duration;
 use log::info;
 
 pub mod http_header;
 pub mod http_request;
 pub mod http_response;
+pub mod cookie;
+pub mod websocket;
+pub mod client;
+mod http_date;
+mod http_message;
+mod sha1;
 mod to_bytes;
 
 use http_header::*;
 use http_request::HttpRequest;
 use http_response::HttpResponse;
+use websocket::WsConnection;
 
 const CRLF: &str = "\r\n";
 const DOUBLE_CRLF: &str = "\r\n\r\n";
 const DOUBLE_CRLF_ASCII: [u8; 4] = ['\r' as u8, '\n' as u8, '\r' as u8, '\n' as u8];
+const DEFAULT_KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5);
 
 type HttpRequestHandler = fn(&HttpRequest, &mut HttpResponse);
-type HttpStaticHandler = fn(&Path, &mut HttpResponse);
+type HttpStaticHandler = fn(&HttpRequest, &Path, &mut HttpResponse);
+type WsHandler = fn(&HttpRequest, &mut WsConnection);
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum HttpVersion {
@@ -94,10 +104,18 @@ struct HttpStaticPath {
     path: PathBuf
 }
 
+#[derive(Clone)]
+struct HttpWsRoute {
+    url: String,
+    callback: WsHandler
+}
+
 pub struct HttpServer {
     endpoints: Vec<HttpEndpoint>,
     static_paths: Vec<HttpStaticPath>,
-    static_handler: HttpStaticHandler
+    static_handler: HttpStaticHandler,
+    ws_routes: Vec<HttpWsRoute>,
+    keep_alive_timeout: Duration
 }
 
 impl HttpServer {
@@ -105,11 +123,20 @@ impl HttpServer {
         HttpServer {
             endpoints: Vec::new(),
             static_paths: Vec::new(),
-            static_handler: | file, response | {
-                response.send_file(file);
-            }
+            static_handler: | request, file, response | {
+                response.send_file_cached(request, file);
+            },
+            ws_routes: Vec::new(),
+            keep_alive_timeout: DEFAULT_KEEP_ALIVE_TIMEOUT
         }
     }
+    // Sets how long a connection's socket will block waiting for the next
+    // request's header bytes (applied before every `parse_request`, so it
+    // also bounds how long a reused keep-alive connection can sit idle).
+    // Defaults to 5 seconds.
+    pub fn keep_alive(&mut self, timeout: Duration) {
+        self.keep_alive_timeout = timeout;
+    }
     pub fn static_path(&mut self, prefix: &str, path: &Path) {
         let mut prefix = prefix.to_owned();
         if !prefix.ends_with('/') {
@@ -123,6 +150,16 @@ impl HttpServer {
     pub fn static_serve(&mut self, cb: HttpStaticHandler) {
         self.static_handler = cb;
     }
+    // Registers a handler for WebSocket upgrade requests on `url`. The
+    // handler takes over the raw connection after the `101 Switching
+    // Protocols` handshake, so it -- not the keep-alive loop -- owns the
+    // socket's lifetime from that point on.
+    pub fn ws(&mut self, url: &str, callback: WsHandler) {
+        self.ws_routes.push(HttpWsRoute {
+            url: String::from(url),
+            callback
+        });
+    }
     fn add(&mut self, url: &str, method: HttpMethod, cb: HttpRequestHandler) {
         self.endpoints.push(HttpEndpoint {
             url: String::from(url),
@@ -142,19 +179,49 @@ impl HttpServer {
     pub fn delete(&mut self, url: &str, callback: HttpRequestHandler) {
         self.add(url, HttpMethod::DELETE, callback);
     }
+    fn is_timeout(error: &io::Error) -> bool {
+        matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+    }
+    // A bare-bones status line, written directly to the socket: at this point
+    // no HttpRequest exists yet for http_response::new() to build a reply from.
+    fn send_timeout_response(socket: &mut TcpStream) {
+        let response = format!("{} 408 Request Timeout{}Connection: close{}{}",
+            HttpVersion::V1_1.to_str(), CRLF, CRLF, CRLF);
+        let _ = socket.write_all(response.as_bytes());
+    }
     fn parse_request(socket: &mut TcpStream) -> Result<Option<HttpRequest>, Box<dyn Error>> {
         let mut header_buf: Vec<u8> = Vec::new();
         let mut header_read = false;
-        let socket = Read::by_ref(socket);
-        for _byte in socket.bytes() {
-            let byte = _byte?;
-            header_buf.push(byte);
-            if header_buf.ends_with(&DOUBLE_CRLF_ASCII) == true {
-                header_read = true;
-                break;
+        let mut timed_out_mid_header = false;
+        {
+            let socket_ref = Read::by_ref(socket);
+            for _byte in socket_ref.bytes() {
+                let byte = match _byte {
+                    Ok(byte) => byte,
+                    Err(error) if Self::is_timeout(&error) => {
+                        // An idle keep-alive connection times out with no bytes
+                        // read: close quietly below, same as a peer closing the
+                        // socket. A timeout with bytes already in flight means
+                        // the client went quiet mid-header; reply 408 once the
+                        // borrow on `socket` from this loop is released.
+                        timed_out_mid_header = !header_buf.is_empty();
+                        break;
+                    },
+                    Err(error) => return Err(Box::new(error))
+                };
+                header_buf.push(byte);
+                if header_buf.ends_with(&DOUBLE_CRLF_ASCII) == true {
+                    header_read = true;
+                    break;
+                }
             }
         }
 
+        if timed_out_mid_header {
+            Self::send_timeout_response(socket);
+            return Ok(None);
+        }
+
         /* No data read, connection closed by the peer */
         /* So we can silently ignore the connection establishment */
         if header_buf.len() == 0 {
@@ -168,101 +235,228 @@ impl HttpServer {
         }
 
         let header_buf = String::from_utf8(header_buf)?;
+        let mut lines = header_buf.split(CRLF);
 
-        let mut http_version = HttpVersion::UNKNOWN;
-        let mut http_method = HttpMethod::UNKNOWN;
-        let mut req_url = String::new();
-        let mut header = http_header::new();
+        // Parse the first line => GET /url HTTP/1.1
+        let request_line = lines.next().unwrap_or("");
+        let words: Vec<&str> = request_line.split(" ").collect();
+        if words.len() != 3 {
+            let custom_err = io::Error::new(io::ErrorKind::InvalidData, "Invalid HTTP header");
+            return Result::Err(Box::new(custom_err));
+        }
 
-        for (i, line) in header_buf.split(CRLF).enumerate() {
-            if i == 0 {
-                // Parse the first line => GET /url HTTP/1.1
-                let words: Vec<&str> = line.split(" ").collect();
+        let http_method = HttpMethod::from_str(words[0]);
+        let http_version = HttpVersion::from_str(words[2]);
 
-                if words.len() != 3 {
-                    let custom_err = io::Error::new(io::ErrorKind::InvalidData, "Invalid HTTP header");
-                    return Result::Err(Box::new(custom_err));
-                }
+        // Strip the query string off the path before route matching;
+        // it is parsed into its own map.
+        let (req_url, req_query) = match words[1].split_once('?') {
+            Some((path, query_string)) => (path.to_string(), Self::parse_query_string(query_string)),
+            None => (words[1].to_string(), HashMap::new())
+        };
 
-                http_method = HttpMethod::from_str(words[0]);
-                req_url = String::from(words[1]);
-                http_version = HttpVersion::from_str(words[2]);
-            } else {
-                let field_value: Vec<&str> = line.split(":").map(|x| x.trim()).collect();
-                if field_value.len() != 2 {
-                    continue;
-                } else {
-                    // TODO: Deal with the HTTP fields which has multiple values or key-value pairs within the value part
-                    header.set(field_value[0], field_value[1]);
-                }
-            }
-        }
+        let mut header = http_header::new();
+        let remaining_lines: Vec<&str> = lines.collect();
+        http_message::parse_header_fields(&remaining_lines, &mut header);
 
-        let mut content_length = 0;
-        let x = header.get("content-length");
-        if !x.is_empty() {
-            content_length = x.parse::<u32>()?;
-        }
+        let body = http_message::read_body(socket, &header, Some(http_message::MAX_CONTENT_LENGTH))?;
 
-        let mut body: Vec<u8> = Vec::new();
-        if content_length > 0 {
-            for _byte in socket.bytes() {
-                let byte = _byte?;
-                body.push(byte);
-                content_length -= 1;
-                if content_length == 0 {
-                    break;
-                }
-            }
-        }
+        let cookies = cookie::parse_cookie_header(header.get("cookie"));
 
         Ok(Some(HttpRequest {
             http_version,
             method: http_method,
             url: req_url,
+            query: req_query,
+            params: HashMap::new(),
+            cookies,
             header,
             body
         }))
     }
-    fn get_request_handler(endpoints: &Vec<HttpEndpoint>, url: &String, method: HttpMethod) -> Option<HttpRequestHandler> {
+    fn parse_query_string(query_string: &str) -> HashMap<String, String> {
+        let mut query: HashMap<String, String> = HashMap::new();
+        for pair in query_string.split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+            match pair.split_once('=') {
+                Some((key, value)) => { query.insert(key.to_string(), value.to_string()); },
+                None => { query.insert(pair.to_string(), String::new()); }
+            }
+        }
+        query
+    }
+    // Matches a registered route pattern (e.g. "/users/:id" or "/files/*") against
+    // a request path, capturing `:name` segments and a trailing `*` catch-all.
+    fn match_route(pattern: &str, url: &str) -> Option<HashMap<String, String>> {
+        let pattern_segs: Vec<&str> = pattern.trim_matches('/').split('/').collect();
+        let url_segs: Vec<&str> = url.trim_matches('/').split('/').collect();
+        let mut params: HashMap<String, String> = HashMap::new();
+
+        for (i, seg) in pattern_segs.iter().enumerate() {
+            if let Some(name) = seg.strip_prefix('*') {
+                if !name.is_empty() {
+                    params.insert(name.to_string(), url_segs[i..].join("/"));
+                }
+                return Some(params);
+            }
+
+            let url_seg = url_segs.get(i)?;
+            if let Some(name) = seg.strip_prefix(':') {
+                params.insert(name.to_string(), url_seg.to_string());
+            } else if seg != url_seg {
+                return None;
+            }
+        }
+
+        if pattern_segs.len() != url_segs.len() {
+            return None;
+        }
+
+        Some(params)
+    }
+    fn get_request_handler(endpoints: &Vec<HttpEndpoint>, url: &String, method: HttpMethod) -> Option<(HttpRequestHandler, HashMap<String, String>)> {
+        // Exact, static routes win over parameterized ones.
         for endpoint in endpoints {
             if (method == endpoint.method) && (url == &endpoint.url) {
-                return Some(endpoint.callback);
+                return Some((endpoint.callback, HashMap::new()));
+            }
+        }
+        for endpoint in endpoints {
+            if method != endpoint.method {
+                continue;
+            }
+            if let Some(params) = Self::match_route(&endpoint.url, url) {
+                return Some((endpoint.callback, params));
             }
         }
         None
     }
+    // Decodes a percent-encoded path ("%2e" -> ".", etc.) so that traversal
+    // segments hidden behind escapes are caught by the check below.
+    fn percent_decode(input: &str) -> Option<String> {
+        let bytes = input.as_bytes();
+        let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' {
+                let hex = input.get(i + 1..i + 3)?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            } else {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8(out).ok()
+    }
     fn get_static_file(static_paths: &Vec<HttpStaticPath>, url: &String) -> Option<PathBuf> {
         for sp in static_paths {
             if url.starts_with(&sp.prefix) {
+                let encoded = &url[sp.prefix.len()..];
+                // Reject an encoded path separator outright rather than letting
+                // percent-decoding turn it into a real "/": decoding it first and
+                // re-splitting on "/" would treat it as a genuine segment boundary
+                // instead of the literal character a client asked to serve.
+                if encoded.to_lowercase().contains("%2f") {
+                    return None;
+                }
+
+                let decoded = Self::percent_decode(encoded)?;
+                if decoded.contains('\0') {
+                    return None;
+                }
+
+                // Resolve segment-by-segment instead of handing the decoded
+                // string to PathBuf::push(): that would let a leading "/"
+                // replace the static root outright, and an embedded ".."
+                // would climb back out of it.
                 let mut file_path = PathBuf::from(&sp.path);
-                file_path.push(&url[sp.prefix.len()..]);
-                if file_path.exists() {
-                    return Some(file_path)
+                for segment in decoded.split('/') {
+                    match segment {
+                        "" | "." => continue,
+                        ".." => return None,
+                        _ => file_path.push(segment)
+                    }
+                }
+
+                // Canonicalize and verify containment within the configured root.
+                // The literal ".." filter above stops plain traversal but not a
+                // symlink inside the static root that points outside it; resolving
+                // both paths and checking `starts_with` catches that case too.
+                let root = sp.path.canonicalize().ok()?;
+                let canonical = file_path.canonicalize().ok()?;
+                if canonical.starts_with(&root) {
+                    return Some(canonical)
                 }
             }
         }
         None
     }
+    fn get_ws_handler(ws_routes: &Vec<HttpWsRoute>, url: &String) -> Option<(WsHandler, HashMap<String, String>)> {
+        for route in ws_routes {
+            if let Some(params) = Self::match_route(&route.url, url) {
+                return Some((route.callback, params));
+            }
+        }
+        None
+    }
+    fn is_websocket_upgrade(header: &HttpHeader) -> bool {
+        header.get("connection").to_lowercase().contains("upgrade")
+            && header.get("upgrade").to_lowercase() == "websocket"
+    }
     fn worker_job(mut socket: TcpStream,
                   endpoints: &Vec<HttpEndpoint>,
                   static_paths: &Vec<HttpStaticPath>,
-                  static_handler: &HttpStaticHandler) ->  Result<Option<TcpStream>, Box<dyn Error>> {
+                  static_handler: &HttpStaticHandler,
+                  ws_routes: &Vec<HttpWsRoute>) ->  Result<Option<TcpStream>, Box<dyn Error>> {
 
         let opt_request = HttpServer::parse_request(&mut socket)?;
 
-        let request = match opt_request {
+        let mut request = match opt_request {
             Some(x) => x,
             None => return Ok(None)
         };
 
         info!("Client - {:?} | Request - {} {}", socket.peer_addr().unwrap(), request.method.to_str(), &request.url);
 
-        if let Some(cb)= Self::get_request_handler(endpoints, &request.url, request.method) {
+        if Self::is_websocket_upgrade(&request.header) {
+            if let Some((cb, params)) = Self::get_ws_handler(ws_routes, &request.url) {
+                request.params = params;
+
+                let client_key = request.header.get("sec-websocket-key");
+                if !websocket::is_valid_key(client_key) {
+                    http_response::new(&mut socket, &request).status(400).end()?;
+                    return Ok(None);
+                }
+
+                let mut response = http_response::new(&mut socket, &request);
+                response.status(101);
+                response.header.set("Upgrade", "websocket");
+                response.header.set("Connection", "Upgrade");
+                response.header.set("Sec-WebSocket-Accept", &websocket::accept_key(client_key));
+                response.end()?;
+
+                // The keep-alive read timeout only bounds how long the HTTP
+                // loop waits for the next request's header; a WebSocket
+                // connection is long-lived and its handler, not that loop,
+                // now owns the socket, so clear it before handing over.
+                socket.set_read_timeout(None)?;
+                cb(&request, &mut websocket::new(&mut socket));
+
+                // The handler owns the connection from here; the HTTP
+                // keep-alive loop has nothing left to read.
+                return Ok(None);
+            }
+        }
+
+        if let Some((cb, params)) = Self::get_request_handler(endpoints, &request.url, request.method) {
+            request.params = params;
             cb(&request, &mut http_response::new(&mut socket, &request));
         }
         else if let Some(file_path) = Self::get_static_file(static_paths, &request.url) {
-            static_handler(&file_path, &mut http_response::new(&mut socket, &request));
+            static_handler(&request, &file_path, &mut http_response::new(&mut socket, &request));
         }
         else {
             http_response::new(&mut socket, &request).status(404).end();
@@ -282,6 +476,8 @@ impl HttpServer {
         let endpoints = Arc::new(self.endpoints.clone());
         let static_paths = Arc::new(self.static_paths.clone());
         let static_handler = Arc::new(self.static_handler);
+        let ws_routes = Arc::new(self.ws_routes.clone());
+        let keep_alive_timeout = self.keep_alive_timeout;
 
         let listener = TcpListener::bind((ip, port))?;
         /* TcpListener::incoming() does accept() & returns the Result<TcpStream> */
@@ -291,11 +487,16 @@ impl HttpServer {
             let endpoints = endpoints.clone();
             let static_paths = static_paths.clone();
             let static_handler = static_handler.clone();
+            let ws_routes = ws_routes.clone();
 
             thread::spawn(move || {
                 info!("Connected to the client - {:?}", socket.peer_addr().unwrap());
                 loop {
-                    let result = Self::worker_job(socket, &endpoints, &static_paths, &static_handler);
+                    if let Err(error) = socket.set_read_timeout(Some(keep_alive_timeout)) {
+                        eprintln!("Error: {:?}", error);
+                        break;
+                    }
+                    let result = Self::worker_job(socket, &endpoints, &static_paths, &static_handler, &ws_routes);
                     match result {
                         Ok(opt_socket) => {
                             match opt_socket {
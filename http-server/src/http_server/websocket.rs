@@ -0,0 +1,189 @@
+use std::io;
+use std::io::{ Read, Write };
+use std::net::TcpStream;
+
+use super::sha1;
+
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+
+        out.push(BASE64_CHARS[(b0 >> 2) as usize] as char);
+        out.push(BASE64_CHARS[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+        out.push(if chunk.len() > 1 { BASE64_CHARS[(((b1 & 0x0F) << 2) | (b2 >> 6)) as usize] as char } else { '=' });
+        out.push(if chunk.len() > 2 { BASE64_CHARS[(b2 & 0x3F) as usize] as char } else { '=' });
+    }
+    out
+}
+
+fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let data = data.trim_end_matches('=');
+    let mut out = Vec::with_capacity(data.len() * 3 / 4 + 3);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for ch in data.bytes() {
+        let value = (match ch {
+            b'A'..=b'Z' => ch - b'A',
+            b'a'..=b'z' => ch - b'a' + 26,
+            b'0'..=b'9' => ch - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None
+        }) as u32;
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+// A `Sec-WebSocket-Key` is required to be 16 bytes of data, base64-encoded.
+pub fn is_valid_key(client_key: &str) -> bool {
+    !client_key.is_empty() && base64_decode(client_key).map_or(false, |bytes| bytes.len() == 16)
+}
+
+// `Sec-WebSocket-Accept` per RFC 6455 4.2.2: base64(SHA1(key + the fixed GUID)).
+pub fn accept_key(client_key: &str) -> String {
+    let digest = sha1::sha1(format!("{}{}", client_key, WS_GUID).as_bytes());
+    base64_encode(&digest)
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum WsOpcode {
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+    Other(u8)
+}
+
+impl WsOpcode {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0x1 => Self::Text,
+            0x2 => Self::Binary,
+            0x8 => Self::Close,
+            0x9 => Self::Ping,
+            0xA => Self::Pong,
+            other => Self::Other(other)
+        }
+    }
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Text => 0x1,
+            Self::Binary => 0x2,
+            Self::Close => 0x8,
+            Self::Ping => 0x9,
+            Self::Pong => 0xA,
+            Self::Other(value) => value
+        }
+    }
+}
+
+pub struct WsMessage {
+    pub opcode: WsOpcode,
+    pub payload: Vec<u8>
+}
+
+// A handshaken WebSocket connection (RFC 6455), handed to a `server.ws(...)`
+// handler after the `101 Switching Protocols` response has been sent.
+pub struct WsConnection<'a> {
+    socket: &'a mut TcpStream
+}
+
+pub fn new<'a>(socket: &'a mut TcpStream) -> WsConnection<'a> {
+    WsConnection { socket }
+}
+
+// Upper bound on a single frame's declared payload length. Without this, the
+// 64-bit extended-length case (`len == 127`) lets a client claim up to
+// `u64::MAX` and have `read_message` allocate that much memory before a
+// single payload byte arrives -- the same unbounded-allocation problem
+// already fixed once for HTTP bodies in `http_message::read_fixed_body`.
+const MAX_PAYLOAD_LEN: u64 = 10 * 1024 * 1024;
+
+impl<'a> WsConnection<'a> {
+    // Reads one frame and unmasks its payload. Frames sent client -> server
+    // are always masked; fragmented messages are not reassembled here.
+    pub fn read_message(&mut self) -> Result<WsMessage, io::Error> {
+        let mut header = [0u8; 2];
+        self.socket.read_exact(&mut header)?;
+
+        let opcode = WsOpcode::from_u8(header[0] & 0x0F);
+        let masked = header[1] & 0x80 != 0;
+        let mut len = (header[1] & 0x7F) as u64;
+
+        if len == 126 {
+            let mut ext = [0u8; 2];
+            self.socket.read_exact(&mut ext)?;
+            len = u16::from_be_bytes(ext) as u64;
+        } else if len == 127 {
+            let mut ext = [0u8; 8];
+            self.socket.read_exact(&mut ext)?;
+            len = u64::from_be_bytes(ext);
+        }
+
+        if len > MAX_PAYLOAD_LEN {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "WebSocket frame payload exceeds maximum accepted size"));
+        }
+
+        let mut mask_key = [0u8; 4];
+        if masked {
+            self.socket.read_exact(&mut mask_key)?;
+        }
+
+        let mut payload = vec![0u8; len as usize];
+        self.socket.read_exact(&mut payload)?;
+
+        if masked {
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask_key[i % 4];
+            }
+        }
+
+        Ok(WsMessage { opcode, payload })
+    }
+    // Server -> client frames are sent unmasked, per RFC 6455 5.1.
+    fn write_frame(&mut self, opcode: WsOpcode, payload: &[u8]) -> Result<(), io::Error> {
+        let mut frame = Vec::with_capacity(payload.len() + 10);
+        frame.push(0x80 | opcode.to_u8());
+
+        if payload.len() < 126 {
+            frame.push(payload.len() as u8);
+        } else if payload.len() <= u16::MAX as usize {
+            frame.push(126);
+            frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        } else {
+            frame.push(127);
+            frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        }
+
+        frame.extend_from_slice(payload);
+        self.socket.write_all(&frame)
+    }
+    pub fn send_text(&mut self, text: &str) -> Result<(), io::Error> {
+        self.write_frame(WsOpcode::Text, text.as_bytes())
+    }
+    pub fn send_binary(&mut self, data: &[u8]) -> Result<(), io::Error> {
+        self.write_frame(WsOpcode::Binary, data)
+    }
+    pub fn send_ping(&mut self, data: &[u8]) -> Result<(), io::Error> {
+        self.write_frame(WsOpcode::Ping, data)
+    }
+    pub fn send_pong(&mut self, data: &[u8]) -> Result<(), io::Error> {
+        self.write_frame(WsOpcode::Pong, data)
+    }
+    pub fn close(&mut self) -> Result<(), io::Error> {
+        self.write_frame(WsOpcode::Close, &[])
+    }
+}